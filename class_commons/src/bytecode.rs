@@ -0,0 +1,220 @@
+//! Helpers for walking raw bytecode without decoding it into instructions.
+
+use std::convert::TryInto;
+use std::fmt;
+
+const TABLESWITCH: u8 = 0xaa;
+const LOOKUPSWITCH: u8 = 0xab;
+const WIDE: u8 = 0xc4;
+const IINC: u8 = 0x84;
+
+/// Errors produced while walking raw bytecode.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BytecodeError {
+    /// The instruction at `pc` runs past the end of the code array.
+    UnexpectedEnd { pc: usize },
+    /// The byte at `pc` is not an opcode that may appear in a class file.
+    UnknownOpcode { pc: usize, opcode: u8 },
+    /// The `wide` prefix at `pc` modifies an opcode it cannot widen.
+    InvalidWideOpcode { pc: usize, opcode: u8 },
+    /// The switch at `pc` declares a negative number of targets.
+    InvalidSwitch { pc: usize },
+}
+
+impl fmt::Display for BytecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BytecodeError::UnexpectedEnd { pc } => {
+                write!(f, "instruction at pc {} runs past the end of the code", pc)
+            }
+            BytecodeError::UnknownOpcode { pc, opcode } => {
+                write!(f, "unknown opcode {:#04x} at pc {}", opcode, pc)
+            }
+            BytecodeError::InvalidWideOpcode { pc, opcode } => {
+                write!(
+                    f,
+                    "opcode {:#04x} cannot be widened (wide at pc {})",
+                    opcode, pc
+                )
+            }
+            BytecodeError::InvalidSwitch { pc } => {
+                write!(f, "switch at pc {} has a negative target count", pc)
+            }
+        }
+    }
+}
+
+impl std::error::Error for BytecodeError {}
+
+/// Length in bytes, opcode included, of every fixed-size instruction.
+///
+/// Returns `None` for the variable-length `tableswitch`, `lookupswitch` and
+/// `wide`, as well as for reserved and unassigned opcodes.
+fn fixed_length(opcode: u8) -> Option<usize> {
+    match opcode {
+        // nop, aconst_null, iconst_*, lconst_*, fconst_*, dconst_*
+        0x00..=0x0f => Some(1),
+        // bipush
+        0x10 => Some(2),
+        // sipush
+        0x11 => Some(3),
+        // ldc
+        0x12 => Some(2),
+        // ldc_w, ldc2_w
+        0x13..=0x14 => Some(3),
+        // iload, lload, fload, dload, aload
+        0x15..=0x19 => Some(2),
+        // *load_<n>, *aload
+        0x1a..=0x35 => Some(1),
+        // istore, lstore, fstore, dstore, astore
+        0x36..=0x3a => Some(2),
+        // *store_<n>, *astore, stack manipulation, arithmetic
+        0x3b..=0x83 => Some(1),
+        // iinc
+        IINC => Some(3),
+        // conversions and comparisons
+        0x85..=0x98 => Some(1),
+        // if*, goto, jsr
+        0x99..=0xa8 => Some(3),
+        // ret
+        0xa9 => Some(2),
+        // *return
+        0xac..=0xb1 => Some(1),
+        // getstatic, putstatic, getfield, putfield, invokevirtual,
+        // invokespecial, invokestatic
+        0xb2..=0xb8 => Some(3),
+        // invokeinterface, invokedynamic
+        0xb9..=0xba => Some(5),
+        // new
+        0xbb => Some(3),
+        // newarray
+        0xbc => Some(2),
+        // anewarray
+        0xbd => Some(3),
+        // arraylength, athrow
+        0xbe..=0xbf => Some(1),
+        // checkcast, instanceof
+        0xc0..=0xc1 => Some(3),
+        // monitorenter, monitorexit
+        0xc2..=0xc3 => Some(1),
+        // multianewarray
+        0xc5 => Some(4),
+        // ifnull, ifnonnull
+        0xc6..=0xc7 => Some(3),
+        // goto_w, jsr_w
+        0xc8..=0xc9 => Some(5),
+        _ => None,
+    }
+}
+
+fn read_i32(code: &[u8], offset: usize, pc: usize) -> Result<i32, BytecodeError> {
+    code.get(offset..offset + 4)
+        .map(|bytes| i32::from_be_bytes(bytes.try_into().unwrap()))
+        .ok_or(BytecodeError::UnexpectedEnd { pc })
+}
+
+/// Returns the length in bytes of the instruction starting at `pc`.
+///
+/// `code` must be the whole code array of a method, since the padding of
+/// `tableswitch` and `lookupswitch` aligns their operands to a multiple of
+/// four bytes from the start of the method.
+pub fn instruction_length(code: &[u8], pc: usize) -> Result<usize, BytecodeError> {
+    let opcode = *code.get(pc).ok_or(BytecodeError::UnexpectedEnd { pc })?;
+
+    let length = match opcode {
+        TABLESWITCH | LOOKUPSWITCH => {
+            let operands = pc + 1 + (4 - (pc + 1) % 4) % 4;
+            let targets = if opcode == TABLESWITCH {
+                let low = i64::from(read_i32(code, operands + 4, pc)?);
+                let high = i64::from(read_i32(code, operands + 8, pc)?);
+                if high < low {
+                    return Err(BytecodeError::InvalidSwitch { pc });
+                }
+                // default, low and high, followed by one offset per case
+                3 * 4 + (high - low + 1) as usize * 4
+            } else {
+                let pairs = read_i32(code, operands + 4, pc)?;
+                if pairs < 0 {
+                    return Err(BytecodeError::InvalidSwitch { pc });
+                }
+                // default and npairs, followed by match-offset pairs
+                2 * 4 + pairs as usize * 8
+            };
+            operands - pc + targets
+        }
+        WIDE => {
+            let widened = *code
+                .get(pc + 1)
+                .ok_or(BytecodeError::UnexpectedEnd { pc })?;
+            match widened {
+                IINC => 6,
+                0x15..=0x19 | 0x36..=0x3a | 0xa9 => 4,
+                _ => {
+                    return Err(BytecodeError::InvalidWideOpcode {
+                        pc,
+                        opcode: widened,
+                    })
+                }
+            }
+        }
+        _ => fixed_length(opcode).ok_or(BytecodeError::UnknownOpcode { pc, opcode })?,
+    };
+
+    if pc + length > code.len() {
+        return Err(BytecodeError::UnexpectedEnd { pc });
+    }
+    Ok(length)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walks_fixed_wide_and_switch_instructions() {
+        let code = [
+            0x10, 0x05, // 0: bipush 5
+            0xc4, 0x84, 0x01, 0x00, 0x00, 0x10, // 2: wide iinc 256, 16
+            0xaa, 0x00, 0x00, 0x00, // 8: tableswitch, padded to 12
+            0x00, 0x00, 0x00, 0x10, // default
+            0x00, 0x00, 0x00, 0x01, // low
+            0x00, 0x00, 0x00, 0x02, // high
+            0x00, 0x00, 0x00, 0x10, // case 1
+            0x00, 0x00, 0x00, 0x10, // case 2
+            0xab, 0x00, 0x00, 0x00, // 32: lookupswitch, padded to 36
+            0x00, 0x00, 0x00, 0x08, // default
+            0x00, 0x00, 0x00, 0x01, // npairs
+            0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, 0x08, // 7 => +8
+            0xb1, // 52: return
+        ];
+        let mut pc = 0;
+        let mut starts = Vec::new();
+        while pc < code.len() {
+            starts.push(pc);
+            pc += instruction_length(&code, pc).unwrap();
+        }
+        assert_eq!(starts, vec![0, 2, 8, 32, 52]);
+    }
+
+    #[test]
+    fn rejects_truncated_and_unknown_instructions() {
+        assert_eq!(
+            instruction_length(&[0xb9, 0x00, 0x01], 0),
+            Err(BytecodeError::UnexpectedEnd { pc: 0 })
+        );
+        assert_eq!(
+            instruction_length(&[0xca], 0),
+            Err(BytecodeError::UnknownOpcode {
+                pc: 0,
+                opcode: 0xca
+            })
+        );
+        assert_eq!(
+            instruction_length(&[0xc4, 0x10, 0x00], 0),
+            Err(BytecodeError::InvalidWideOpcode {
+                pc: 0,
+                opcode: 0x10
+            })
+        );
+    }
+}
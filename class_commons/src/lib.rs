@@ -1,3 +1,6 @@
+pub mod bytecode;
+pub mod names;
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -0,0 +1,114 @@
+//! Conversions between the internal form of class names used inside class files
+//! (`java/lang/String`, `[Ljava/lang/String;`) and the binary form used by Java
+//! source and reflection (`java.lang.String`, `java.lang.String[]`).
+
+/// Converts an internal class name, or an array descriptor, into its binary name.
+///
+/// `java/lang/String` becomes `java.lang.String` and `[[I` becomes `int[][]`.
+pub fn internal_to_binary(name: &str) -> String {
+    let dimensions = name.chars().take_while(|&c| c == '[').count();
+    if dimensions == 0 {
+        return name.replace('/', ".");
+    }
+
+    let element = &name[dimensions..];
+    let mut binary = match element {
+        "B" => "byte".to_string(),
+        "C" => "char".to_string(),
+        "D" => "double".to_string(),
+        "F" => "float".to_string(),
+        "I" => "int".to_string(),
+        "J" => "long".to_string(),
+        "S" => "short".to_string(),
+        "Z" => "boolean".to_string(),
+        _ => {
+            let class = element
+                .strip_prefix('L')
+                .and_then(|e| e.strip_suffix(';'))
+                .unwrap_or(element);
+            class.replace('/', ".")
+        }
+    };
+    for _ in 0..dimensions {
+        binary.push_str("[]");
+    }
+    binary
+}
+
+/// Converts a binary class name into its internal form.
+///
+/// `java.lang.String` becomes `java/lang/String` and `java.lang.String[]`
+/// becomes the array descriptor `[Ljava/lang/String;`.
+pub fn binary_to_internal(name: &str) -> String {
+    let mut element = name;
+    let mut dimensions = 0;
+    while let Some(stripped) = element.strip_suffix("[]") {
+        element = stripped;
+        dimensions += 1;
+    }
+    if dimensions == 0 {
+        return name.replace('.', "/");
+    }
+
+    let mut internal = "[".repeat(dimensions);
+    match element {
+        "byte" => internal.push('B'),
+        "char" => internal.push('C'),
+        "double" => internal.push('D'),
+        "float" => internal.push('F'),
+        "int" => internal.push('I'),
+        "long" => internal.push('J'),
+        "short" => internal.push('S'),
+        "boolean" => internal.push('Z'),
+        _ => {
+            internal.push('L');
+            internal.push_str(&element.replace('.', "/"));
+            internal.push(';');
+        }
+    }
+    internal
+}
+
+/// Returns the binary name of the package an internal class name belongs to.
+///
+/// `java/lang/String` yields `java.lang`. Classes in the default package and
+/// array descriptors, which have no package, yield `None`.
+pub fn package_name(internal_name: &str) -> Option<String> {
+    if internal_name.starts_with('[') {
+        return None;
+    }
+    internal_name
+        .rfind('/')
+        .map(|slash| internal_name[..slash].replace('/', "."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_plain_and_array_names_both_ways() {
+        let pairs = [
+            ("java/lang/String", "java.lang.String"),
+            ("java/util/Map$Entry", "java.util.Map$Entry"),
+            ("Foo", "Foo"),
+            ("[Ljava/lang/String;", "java.lang.String[]"),
+            ("[[I", "int[][]"),
+            ("[Z", "boolean[]"),
+        ];
+        for (internal, binary) in pairs.iter() {
+            assert_eq!(internal_to_binary(internal), *binary);
+            assert_eq!(binary_to_internal(binary), *internal);
+        }
+    }
+
+    #[test]
+    fn package_name_skips_default_package_and_arrays() {
+        assert_eq!(
+            package_name("java/util/Map$Entry"),
+            Some("java.util".to_string())
+        );
+        assert_eq!(package_name("Foo"), None);
+        assert_eq!(package_name("[Ljava/lang/String;"), None);
+    }
+}